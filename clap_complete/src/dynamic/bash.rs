@@ -110,6 +110,9 @@ pub struct CompleteArgs {
     #[clap(long, conflicts_with = "space", hide_short_help = true, action)]
     no_space: bool,
 
+    #[clap(long, hide_short_help = true, action)]
+    descriptions: bool,
+
     #[clap(raw = true, hide_short_help = true, value_parser)]
     comp_words: Vec<OsString>,
 }
@@ -125,6 +128,10 @@ pub enum Behavior {
     Minimal,
     /// Fallback to readline behavior when no matches are generated
     Readline,
+    /// Like [`Behavior::Readline`], but also request value descriptions from the completer
+    /// and, like cobra's bash completion V2, annotate multi-candidate completions with them
+    /// instead of discarding them.
+    Descriptions,
     /// Customize bash's completion behavior
     Custom(String),
 }
@@ -161,19 +168,141 @@ pub fn register(
     let options = match behavior {
         Behavior::Minimal => "-o nospace -o bashdefault",
         Behavior::Readline => "-o nospace -o default -o bashdefault",
+        Behavior::Descriptions => "-o nospace -o default -o bashdefault",
         Behavior::Custom(c) => c.as_str(),
     };
 
     let completer = shlex::quote(completer);
 
-    let script = r#"
+    let debug_fn = r#"
 __clap_complete_NAME_debug() {
     local file="$BASH_COMP_DEBUG_FILE"
     if [[ -n ${file} ]]; then
         echo "$*" >> "${file}"
     fi
 }
+"#;
+
+    let body = if matches!(behavior, Behavior::Descriptions) {
+        r#"
+_clap_complete_NAME() {
+    local compCmd
+    local IFS=$'\013'
+    local SUPPRESS_SPACE=0
+    if compopt +o nospace 2> /dev/null; then
+        SUPPRESS_SPACE=1
+    fi
+    if [[ ${SUPPRESS_SPACE} == 1 ]]; then
+        SPACE_ARG="--no-space"
+    else
+        SPACE_ARG="--space"
+    fi
+
+    compCmd="COMPLETER complete --index ${COMP_CWORD} --type ${COMP_TYPE} ${SPACE_ARG} --descriptions --ifs=$IFS -- ${COMP_WORDS[@]}"
+
+    __clap_complete_NAME_debug "Calling completion command: eval ${compCmd}"
+
+    local rawOutput
+    rawOutput=$("COMPLETER" complete --index ${COMP_CWORD} --type ${COMP_TYPE} ${SPACE_ARG} --descriptions --ifs="$IFS" -- "${COMP_WORDS[@]}")
+    local compStatus=$?
+    __clap_complete_NAME_debug "Completion command output: ${rawOutput}"
+
+    if [[ $compStatus != 0 ]]; then
+        unset COMPREPLY
+        return
+    fi
+
+    local -a rawLines
+    IFS="$IFS" read -r -a rawLines <<< "$rawOutput"
+
+    # The last two entries are always directive metadata: a comma-separated list of file
+    # extensions (blank if none), then the flag bits; pop them off before turning the rest
+    # into completions. The flag bits come last (not the extensions) because `read -a` drops
+    # a *trailing* empty field, and the extensions are blank in the common case where no
+    # `file_ext` was set - putting the never-blank flag bits last keeps that field from
+    # vanishing and shifting the real last candidate into its place.
+    local directive="${rawLines[-1]-:0}"
+    directive=${directive#:}
+    unset 'rawLines[-1]'
+    local fileExts="${rawLines[-1]-}"
+    unset 'rawLines[-1]'
+    __clap_complete_NAME_debug "Completion directive: ${directive}, fileExts: ${fileExts}"
+
+    # Each remaining line is "value<TAB>description"; split the two apart, like cobra's
+    # bash completion V2.
+    local -a values descriptions
+    local line value desc tab
+    tab="$(printf '\t')"
+    for line in "${rawLines[@]}"; do
+        value=${line%%"$tab"*}
+        if [[ "$line" == *"$tab"* ]]; then
+            desc=${line#*"$tab"}
+        else
+            desc=""
+        fi
+        values+=("$value")
+        descriptions+=("$desc")
+    done
+
+    # Compute the longest common prefix across all candidate values, like cobra's bash
+    # completion V2, so a set of candidates that all collapse to the same value completes
+    # directly instead of popping up a menu of one.
+    local lcp="${values[0]-}"
+    local v
+    for v in "${values[@]:1}"; do
+        while [[ -n "$lcp" && "${v:0:${#lcp}}" != "$lcp" ]]; do
+            lcp="${lcp%?}"
+        done
+    done
+    local allSameAsLcp=1
+    for v in "${values[@]}"; do
+        [[ "$v" == "$lcp" ]] || { allSameAsLcp=0; break; }
+    done
+    __clap_complete_NAME_debug "Longest common prefix: ${lcp}"
+
+    if [[ ${#values[@]} -gt 0 && $allSameAsLcp == 1 ]]; then
+        # Every candidate collapsed to the same value; complete it directly and drop the
+        # now-useless description.
+        COMPREPLY=("${values[0]}")
+    elif [[ ${#values[@]} -gt 0 ]]; then
+        COMPREPLY=("${values[@]}")
+        if [[ -t 1 ]]; then
+            local i
+            for i in "${!values[@]}"; do
+                [[ -n "${descriptions[$i]}" ]] && printf '%s\t%s\n' "${values[$i]}" "${descriptions[$i]}" >&2
+            done
+        fi
+    else
+        unset COMPREPLY
+    fi
+
+    if [[ $SUPPRESS_SPACE == 1 ]] && { [[ "${COMPREPLY[0]-}" =~ [=/:]$ ]] || (( (directive & 1) != 0 )); }; then
+        compopt -o nospace
+    fi
+
+    if (( (directive & 8) != 0 )); then
+        compopt -o nosort 2> /dev/null
+    fi
 
+    if [[ ${#COMPREPLY[@]} -eq 0 ]] && (( (directive & 2) == 0 )); then
+        local cur="${COMP_WORDS[COMP_CWORD]}"
+        if (( (directive & 4) != 0 )); then
+            COMPREPLY=( $(compgen -d -- "${cur}") )
+        elif [[ -n "$fileExts" ]]; then
+            local ext
+            for ext in ${fileExts//,/ }; do
+                COMPREPLY+=( $(compgen -f -X "!*.${ext}" -- "${cur}") )
+            done
+        fi
+    fi
+
+    if (( (directive & 2) != 0 )); then
+        compopt +o default
+    fi
+}
+"#
+    } else {
+        r#"
 _clap_complete_NAME() {
     local compCmd
     local IFS=$'\013'
@@ -192,22 +321,60 @@ _clap_complete_NAME() {
     __clap_complete_NAME_debug "Calling completion command: eval ${compCmd}"
 
     COMPREPLY=( $("COMPLETER" complete --index ${COMP_CWORD} --type ${COMP_TYPE} ${SPACE_ARG} --ifs="$IFS" -- "${COMP_WORDS[@]}") )
+    local compStatus=$?
     __clap_complete_NAME_debug "Completion command output: ${COMPREPLY}"
 
-    if [[ $? != 0 ]]; then
+    # The last two "words" in COMPREPLY are always directive metadata: a comma-separated
+    # list of file extensions (blank if none), then the flag bits; pop them back off before
+    # using the array as completions. The flag bits come last (not the extensions) because
+    # word-splitting a command substitution drops a *trailing* empty field, and the
+    # extensions are blank in the common case where no `file_ext` was set - putting the
+    # never-blank flag bits last keeps that field from vanishing and shifting the real last
+    # candidate into its place.
+    local directive="${COMPREPLY[-1]-:0}"
+    directive=${directive#:}
+    unset 'COMPREPLY[-1]'
+    local fileExts="${COMPREPLY[-1]-}"
+    unset 'COMPREPLY[-1]'
+    __clap_complete_NAME_debug "Completion directive: ${directive}, fileExts: ${fileExts}"
+
+    if [[ $compStatus != 0 ]]; then
         unset COMPREPLY
-    elif [[ $SUPPRESS_SPACE == 1 ]] && [[ "${COMPREPLY-}" =~ [=/:]$ ]]; then
-        compopt -o nospace
+    else
+        if [[ $SUPPRESS_SPACE == 1 ]] && { [[ "${COMPREPLY-}" =~ [=/:]$ ]] || (( (directive & 1) != 0 )); }; then
+            compopt -o nospace
+        fi
+
+        if (( (directive & 8) != 0 )); then
+            compopt -o nosort 2> /dev/null
+        fi
+
+        if [[ ${#COMPREPLY[@]} -eq 0 ]] && (( (directive & 2) == 0 )); then
+            local cur="${COMP_WORDS[COMP_CWORD]}"
+            if (( (directive & 4) != 0 )); then
+                COMPREPLY=( $(compgen -d -- "${cur}") )
+            elif [[ -n "$fileExts" ]]; then
+                local ext
+                for ext in ${fileExts//,/ }; do
+                    COMPREPLY+=( $(compgen -f -X "!*.${ext}" -- "${cur}") )
+                done
+            fi
+        fi
     fi
-}
 
-complete OPTIONS -F _clap_complete_NAME EXECUTABLES
+    if (( (directive & 2) != 0 )); then
+        compopt +o default
+    fi
+}
 "#
-    .replace("NAME", &escaped_name)
-    .replace("EXECUTABLES", &executables)
-    .replace("OPTIONS", options)
-    .replace("COMPLETER", &completer)
-    .replace("UPPER", &upper_name);
+    };
+
+    let script = format!("{}{}\ncomplete OPTIONS -F _clap_complete_NAME EXECUTABLES\n", debug_fn, body)
+        .replace("NAME", &escaped_name)
+        .replace("EXECUTABLES", &executables)
+        .replace("OPTIONS", options)
+        .replace("COMPLETER", &completer)
+        .replace("UPPER", &upper_name);
 
     writeln!(buf, "{}", script)?;
     Ok(())
@@ -228,17 +395,44 @@ pub fn complete(cmd: &mut clap::Command, args: &CompleteArgs) -> clap::Result<()
     .unwrap();
 
     let current_dir = std::env::current_dir().ok();
-    let completions =
+    let (completions, directive) =
         super::complete::get(cmd, args.comp_words.clone(), index, current_dir.as_deref())?;
+    let completions =
+        super::complete_stacked_short_flags(cmd, &args.comp_words, index, completions);
+
+    let ifs = args.ifs.as_deref().unwrap_or("\n");
+    let mut parts = Vec::with_capacity(completions.len() + 2);
+    for completion in completions.iter() {
+        let value = completion.to_string_lossy();
+        if args.descriptions {
+            match super::find_help(cmd, &value) {
+                Some(help) => parts.push(format!("{}\t{}", value, help)),
+                None => parts.push(value.into_owned()),
+            }
+        } else {
+            parts.push(value.into_owned());
+        }
+    }
+    // The last two entries are always directive metadata: a comma-separated list of file
+    // extensions (blank if none), then the flag bits. They're always present so the
+    // registration script can unconditionally treat the trailing two entries as the
+    // directive instead of having to guess whether they're there - and extensions come
+    // *before* the flag bits, not after, since `$()` command substitution strips trailing
+    // empty fields/lines in every shell we target, and the extensions entry is blank in the
+    // common case where no `file_ext` was set. The flag bits are never blank, so they're
+    // safe to put last.
+    let (directive_exts, directive_flags) = directive.encode();
+    parts.push(directive_exts);
+    parts.push(directive_flags);
 
     let mut buf = Vec::new();
-    for (i, completion) in completions.iter().enumerate() {
+    for (i, part) in parts.iter().enumerate() {
         if i != 0 {
-            write!(&mut buf, "{}", args.ifs.as_deref().unwrap_or("\n"))?;
+            write!(&mut buf, "{}", ifs)?;
         }
-        write!(&mut buf, "{}", completion.to_string_lossy())?;
+        write!(&mut buf, "{}", part)?;
     }
-    std::io::stdout().write(&buf)?;
+    std::io::stdout().write_all(&buf)?;
 
     Ok(())
 }
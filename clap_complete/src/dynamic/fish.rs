@@ -0,0 +1,151 @@
+use std::{ffi::OsString, io::Write};
+
+use unicode_xid::UnicodeXID;
+
+#[derive(Clone, Debug, clap::Args)]
+#[allow(missing_docs)]
+pub struct CompleteArgs {
+    #[clap(
+        long,
+        required = true,
+        value_name = "COMP_CWORD",
+        hide_short_help = true,
+        value_parser
+    )]
+    index: Option<usize>,
+
+    #[clap(long, hide_short_help = true, action)]
+    space: bool,
+
+    #[clap(long, conflicts_with = "space", hide_short_help = true, action)]
+    no_space: bool,
+
+    #[clap(raw = true, hide_short_help = true, value_parser)]
+    comp_words: Vec<OsString>,
+}
+
+/// The recommended file name for the registration code
+pub fn file_name(name: &str) -> String {
+    format!("{}.fish", name)
+}
+
+/// Generate code to register the dynamic completion
+pub fn register(
+    name: &str,
+    executables: impl IntoIterator<Item = impl AsRef<str>>,
+    completer: &str,
+    buf: &mut dyn Write,
+) -> Result<(), std::io::Error> {
+    let escaped_name = name.replace('-', "_");
+    debug_assert!(
+        escaped_name.chars().all(|c| c.is_xid_continue()),
+        "`name` must be an identifier, got `{}`",
+        escaped_name
+    );
+
+    let executables = executables
+        .into_iter()
+        .map(|s| shlex::quote(s.as_ref()).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let completer = shlex::quote(completer);
+
+    let script = r#"
+function __clap_complete_NAME
+    set -l tokens (commandline -opc) (commandline -ct)
+    set -l index (count $tokens)
+    set -l compResult (COMPLETER complete --index $index --no-space -- $tokens)
+
+    # The last two lines are always directive metadata: a comma-separated list of file
+    # extensions (blank if none), then the flag bits; split them off before handing the rest
+    # to fish's pager. The flag bits are last (not the extensions) because fish's command
+    # substitution drops a trailing empty line, and the extensions line is blank in the
+    # common case where no `file_ext` was set; the flag bits are never blank, so putting them
+    # last keeps that line from vanishing and shifting the real last candidate into its
+    # place.
+    set -l directive (string replace -r '^:' '' -- $compResult[-1])
+    set -e compResult[-1]
+    set -l fileExts $compResult[-1]
+    set -e compResult[-1]
+
+    set -l noFileComp 0
+    set -l noSpace 0
+    if test -n "$directive"
+        test (math "$directive & 2") -ne 0; and set noFileComp 1
+        test (math "$directive & 1") -ne 0; and set noSpace 1
+    end
+
+    if test $noSpace -eq 1 -a (count $compResult) -eq 1
+        # fish always inserts a trailing space once the only remaining candidate is
+        # accepted, and has no per-candidate "no trailing space" flag to override that; work
+        # around it by inserting the value onto the command line ourselves instead of
+        # returning it as a completion candidate, so fish never gets the chance to add one.
+        set -l value (string split -m 1 \t -- $compResult[1])[1]
+        commandline -rt -- $value
+        return 1
+    end
+
+    for line in $compResult
+        echo $line
+    end
+
+    # `-f` below always suppresses fish's own native file completion so that we have full
+    # control; when our own candidate list is empty and the directive allows falling back to
+    # file completion, do it ourselves instead of leaving the user with nothing.
+    if test (count $compResult) -eq 0 -a $noFileComp -eq 0
+        __fish_complete_path (commandline -ct)
+    end
+end
+
+for exe in EXECUTABLES
+    complete -c $exe -f -a '(__clap_complete_NAME)'
+end
+"#
+    .replace("NAME", &escaped_name)
+    .replace("EXECUTABLES", &executables)
+    .replace("COMPLETER", &completer);
+
+    writeln!(buf, "{}", script)?;
+    Ok(())
+}
+
+/// Process the completion request for fish
+pub fn complete(cmd: &mut clap::Command, args: &CompleteArgs) -> clap::Result<()> {
+    let index = args.index.unwrap_or_default();
+    let _space = match (args.space, args.no_space) {
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        (true, true) => {
+            unreachable!("`--space` and `--no-space` set, clap should prevent this")
+        }
+        (false, false) => None,
+    }
+    .unwrap();
+
+    let current_dir = std::env::current_dir().ok();
+    let (completions, directive) =
+        super::complete::get(cmd, args.comp_words.clone(), index, current_dir.as_deref())?;
+    let completions =
+        super::complete_stacked_short_flags(cmd, &args.comp_words, index, completions);
+
+    let mut buf = Vec::new();
+    for completion in completions.iter() {
+        let value = completion.to_string_lossy();
+        match super::find_help(cmd, &value) {
+            Some(help) => writeln!(&mut buf, "{}\t{}", value, help)?,
+            None => writeln!(&mut buf, "{}", value)?,
+        }
+    }
+    // The last two lines are always directive metadata: a comma-separated list of file
+    // extensions (blank if none), then the flag bits. Extensions come *before* the flag
+    // bits, not after, since fish's command substitution strips a trailing empty line and
+    // the extensions line is blank in the common case where no `file_ext` was set; the flag
+    // bits are never blank, so they're safe to put last.
+    let (directive_exts, directive_flags) = directive.encode();
+    writeln!(&mut buf, "{}", directive_exts)?;
+    writeln!(&mut buf, "{}", directive_flags)?;
+    std::io::stdout().write_all(&buf)?;
+
+    Ok(())
+}
@@ -6,6 +6,8 @@ use std::io::Write;
 pub mod bash;
 /// Completion code common to all shells
 pub mod complete;
+/// Complete commands within fish
+pub mod fish;
 /// Complete commands within zsh
 pub mod zsh;
 
@@ -36,6 +38,7 @@ pub struct CompletionsArgs {
 #[allow(missing_docs)]
 pub enum CompletionsShell {
     Bash,
+    Fish,
     Zsh,
 }
 
@@ -43,9 +46,91 @@ pub enum CompletionsShell {
 #[allow(missing_docs)]
 pub enum CompleteShell {
     Bash(bash::CompleteArgs),
+    Fish(fish::CompleteArgs),
     Zsh(zsh::CompleteArgs),
 }
 
+bitflags::bitflags! {
+    /// Flags a shell should honor when presenting a set of completion candidates.
+    #[derive(Default)]
+    struct CompDirectiveFlags: u8 {
+        /// Don't add a trailing space after the candidate, even when it's the only one.
+        const NO_SPACE = 1 << 0;
+        /// Don't fall back to the shell's default file completion when no candidates match.
+        const NO_FILE_COMP = 1 << 1;
+        /// Only offer directory names when falling back to file completion.
+        const DIRS_ONLY = 1 << 2;
+        /// Preserve the order candidates were generated in instead of letting the shell sort them.
+        const KEEP_ORDER = 1 << 3;
+    }
+}
+
+/// Instructions for how a shell should interpret and present a set of completion candidates.
+///
+/// Each shell's `complete` function encodes this alongside the candidate list so the
+/// corresponding registration script can translate it into that shell's own completion
+/// mechanism (`compopt`/`compgen` for bash, `_describe`/`_files` for zsh, `commandline` and
+/// `__fish_complete_path` for fish). The fish script acts only on the no-space and
+/// no-file-completion flags; it ignores the directory-only and keep-order flags and the file
+/// extensions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompDirective {
+    flags: CompDirectiveFlags,
+    file_exts: Vec<String>,
+}
+
+impl CompDirective {
+    /// An empty directive; the shell should use its normal completion behavior.
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    /// Don't add a trailing space after the candidate.
+    pub fn no_space(mut self) -> Self {
+        self.flags.insert(CompDirectiveFlags::NO_SPACE);
+        self
+    }
+
+    /// Don't fall back to the shell's default file completion.
+    pub fn no_file_comp(mut self) -> Self {
+        self.flags.insert(CompDirectiveFlags::NO_FILE_COMP);
+        self
+    }
+
+    /// Only offer directory names when falling back to file completion.
+    pub fn dirs_only(mut self) -> Self {
+        self.flags.insert(CompDirectiveFlags::DIRS_ONLY);
+        self
+    }
+
+    /// Preserve the order candidates were generated in.
+    pub fn keep_order(mut self) -> Self {
+        self.flags.insert(CompDirectiveFlags::KEEP_ORDER);
+        self
+    }
+
+    /// Restrict the file completion fallback to the given extension (without the leading `.`).
+    pub fn file_ext(mut self, ext: impl Into<String>) -> Self {
+        self.file_exts.push(ext.into());
+        self
+    }
+
+    /// Encode this directive as the two trailing entries a registration script parses after
+    /// the list of candidates: a comma-separated list of file extensions (empty if none),
+    /// followed by the flag bits (always a `:`-prefixed integer; the scripts strip the `:`
+    /// before doing arithmetic on it).
+    ///
+    /// Callers MUST write these in the order returned - file extensions *then* flags, never
+    /// the reverse. Command substitution in every shell we target strips trailing empty
+    /// lines/fields, and the extensions entry is empty in the common case where no
+    /// `file_ext` was set; putting it last would make it vanish and shift the real last
+    /// candidate into the flags slot instead. The flag bits are never empty (`bits()` is
+    /// always printed, even when zero), so they're safe to put last.
+    pub(crate) fn encode(&self) -> (String, String) {
+        (self.file_exts.join(","), format!(":{}", self.flags.bits()))
+    }
+}
+
 impl CompleteCommand {
     /// Process the completion request
     pub fn run(&self, cmd: &mut clap::Command) -> std::convert::Infallible {
@@ -62,7 +147,8 @@ impl CompleteCommand {
             Completions(args) => register(cmd, args),
 
             Complete(CompleteShell::Bash(args)) => bash::complete(cmd, args),
-            Complete(CompleteShell::Zsh(_args)) => todo!(),
+            Complete(CompleteShell::Fish(args)) => fish::complete(cmd, args),
+            Complete(CompleteShell::Zsh(args)) => zsh::complete(cmd, args),
         }
     }
 }
@@ -76,6 +162,7 @@ fn register(cmd: &mut clap::Command, args: &CompletionsArgs) -> clap::Result<()>
         CompletionsShell::Bash => {
             bash::register(name, [bin], bin, &bash::Behavior::default(), &mut buf)?
         }
+        CompletionsShell::Fish => fish::register(name, [bin], bin, &mut buf)?,
         CompletionsShell::Zsh => zsh::register(name, [bin], bin, &mut buf)?,
     };
 
@@ -94,6 +181,7 @@ fn register(cmd: &mut clap::Command, args: &CompletionsArgs) -> clap::Result<()>
     if out_path.is_dir() {
         let filename = match args.shell {
             CompletionsShell::Bash => bash::file_name(name),
+            CompletionsShell::Fish => fish::file_name(name),
             CompletionsShell::Zsh => zsh::file_name(name),
         };
 
@@ -105,3 +193,97 @@ fn register(cmd: &mut clap::Command, args: &CompletionsArgs) -> clap::Result<()>
 
     Ok(())
 }
+
+// Walk `comp_words[..index]`, the same traversal `complete::get` performs to pick which
+// command's arguments are in scope, so `complete_stacked_short_flags` stacks the *active*
+// subcommand's short flags instead of always falling back to the root command's.
+fn resolve_subcommand<'c>(
+    cmd: &'c clap::Command,
+    comp_words: &[std::ffi::OsString],
+    index: usize,
+) -> &'c clap::Command {
+    let mut current = cmd;
+    for word in comp_words.iter().take(index) {
+        if let Some(word) = word.to_str() {
+            if let Some(sub) = current.find_subcommand(word) {
+                current = sub;
+            }
+        }
+    }
+    current
+}
+
+// When the word under the cursor bundles several short boolean flags together (`-abc`),
+// `complete::get` treats it as a single opaque token and won't offer to extend the stack.
+// Both the bash and zsh completers route through `complete::get`, so extend its candidates
+// here rather than duplicating this in each shell, the way cobra did for bundled shorthand
+// flags.
+pub(crate) fn complete_stacked_short_flags(
+    cmd: &clap::Command,
+    comp_words: &[std::ffi::OsString],
+    index: usize,
+    mut completions: Vec<std::ffi::OsString>,
+) -> Vec<std::ffi::OsString> {
+    let current = match comp_words.get(index).and_then(|word| word.to_str()) {
+        Some(current) => current,
+        None => return completions,
+    };
+
+    if !current.starts_with('-') || current.starts_with("--") {
+        return completions;
+    }
+
+    let stack = &current[1..];
+    if stack.is_empty() {
+        return completions;
+    }
+
+    let active_cmd = resolve_subcommand(cmd, comp_words, index);
+    let short_bool_flags: Vec<char> = active_cmd
+        .get_arguments()
+        .filter(|arg| !arg.is_takes_value_set())
+        .filter_map(|arg| arg.get_short())
+        .collect();
+
+    if !stack.chars().all(|c| short_bool_flags.contains(&c)) {
+        return completions;
+    }
+
+    for short in &short_bool_flags {
+        if stack.contains(*short) {
+            continue;
+        }
+        completions.push(format!("-{}{}", stack, short).into());
+    }
+
+    completions
+}
+
+// Shared by every shell that annotates candidates with a description (zsh's `_describe`,
+// fish's pager, bash's V2 completions): look up the arg or subcommand a candidate came from
+// and surface its help text.
+pub(crate) fn find_help<'c>(cmd: &'c clap::Command, value: &str) -> Option<&'c str> {
+    for subcommand in cmd.get_subcommands() {
+        if subcommand.get_name() == value
+            || subcommand.get_all_aliases().any(|alias| alias == value)
+        {
+            return subcommand.get_about();
+        }
+    }
+
+    for arg in cmd.get_arguments() {
+        let long_matches = arg
+            .get_long()
+            .map(|long| value == format!("--{}", long))
+            .unwrap_or(false);
+        let short_matches = arg
+            .get_short()
+            .map(|short| value == format!("-{}", short))
+            .unwrap_or(false);
+        if long_matches || short_matches {
+            return arg.get_help();
+        }
+    }
+
+    None
+}
@@ -1,10 +1,32 @@
-use std::io::Write;
+use std::{ffi::OsString, io::Write};
 
 use unicode_xid::UnicodeXID;
 
+use super::bash::CompType;
+
 #[derive(Clone, Debug, clap::Args)]
 #[allow(missing_docs)]
 pub struct CompleteArgs {
+    #[clap(
+        long,
+        required = true,
+        value_name = "COMP_CWORD",
+        hide_short_help = true,
+        value_parser
+    )]
+    index: Option<usize>,
+
+    #[clap(long = "type", required = true, hide_short_help = true, value_parser)]
+    comp_type: Option<CompType>,
+
+    #[clap(long, hide_short_help = true, action)]
+    space: bool,
+
+    #[clap(long, conflicts_with = "space", hide_short_help = true, action)]
+    no_space: bool,
+
+    #[clap(raw = true, hide_short_help = true, value_parser)]
+    comp_words: Vec<OsString>,
 }
 
 /// Generate code to register the dynamic completion
@@ -32,7 +54,6 @@ pub fn register(
     let completer = shlex::quote(completer);
 
     // Adapted from github.com/spf13/cobra
-    // TODO: Implement no-space flag
     let script = r#"
 #compdef _clap_complete_NAME NAME
 # zsh completion for NAME
@@ -80,7 +101,24 @@ _clap_complete_NAME() {
     __clap_complete_NAME_debug "completions: ${compResult}"
     __clap_complete_NAME_debug "flagPrefix: ${flagPrefix}"
 
-    while IFS='\n' read -r compLine; do
+    # Split into an array of lines up front, then pop the last two off as directive metadata:
+    # a comma-separated list of file extensions (blank if none), then the flag bits. Popping
+    # fixed array elements is exact regardless of whether there are zero, one, or many
+    # candidates - unlike trimming a `*$'\n'` suffix off the raw string, it can't mistake the
+    # directive itself for a phantom candidate when the candidate list is empty. The flag
+    # bits are last (not the extensions) because `$(...)` command substitution strips
+    # trailing empty lines, and the extensions line is blank in the common case where no
+    # `file_ext` was set; the flag bits are never blank, so putting them last keeps that
+    # field from vanishing and shifting the real last candidate into its place.
+    local -a resultLines
+    resultLines=("${(@f)compResult}")
+    local directive="${resultLines[-1]#:}"
+    local fileExts="${resultLines[-2]}"
+    resultLines[-1]=()
+    resultLines[-1]=()
+    __clap_complete_NAME_debug "directive: ${directive}, fileExts: ${fileExts}"
+
+    for compLine in "${resultLines[@]}"; do
         if [ -n "$compLine" ]; then
             # If requested, completions are returned with a description.
             # The description is preceded by a TAB character.
@@ -93,24 +131,44 @@ _clap_complete_NAME() {
             completions+=${compLine}
             lastComp=$compLine
         fi
-    done < <(printf "%%s\n" "${compResult[@]}")
+    done
 
     __clap_complete_NAME_debug "Calling _describe"
-    if eval _describe "completions" completions; then
+    local -a describeOpts
+    if (( (directive & 8) != 0 )); then
+        # Preserve the order completions were generated in instead of letting _describe sort.
+        describeOpts+=(-V)
+    fi
+    local noSpace
+    if (( (directive & 1) != 0 )); then
+        # Hand compadd an empty suffix so no space is added after the completion.
+        noSpace="-S ''"
+    fi
+    if eval _describe "${describeOpts[@]}" "completions" completions ${noSpace}; then
         __clap_complete_NAME_debug "_describe found some completions"
         # Return the success of having called _describe
         return 0
     else
         __clap_complete_NAME_debug "_describe did not find completions."
-        __clap_complete_NAME_debug "Checking if we should do file completion."
-        # TODO: Allow customizing behavior here
 
-        # Perform file completion
+        if (( (directive & 2) != 0 )); then
+            __clap_complete_NAME_debug "NO_FILE_COMP set, skipping file completion"
+            return 1
+        fi
+
+        __clap_complete_NAME_debug "Checking if we should do file completion."
         __clap_complete_NAME_debug "Activating file completion"
 
         # We must return the result of this command, so it must be the
         # last command, or else we must store its result to return it.
-        _arguments '*:filename:_files'" ${flagPrefix}"
+        if (( (directive & 4) != 0 )); then
+            _arguments '*:filename:_files -/'" ${flagPrefix}"
+        elif [[ -n "${fileExts}" ]]; then
+            local globExts="${fileExts//,/|}"
+            _arguments '*:filename:_files -g "*.('"${globExts}"')"'" ${flagPrefix}"
+        else
+            _arguments '*:filename:_files'" ${flagPrefix}"
+        fi
     fi
 }
 
@@ -132,3 +190,44 @@ fi
 pub fn file_name(name: &str) -> String {
     format!("{}.zsh", name)
 }
+
+/// Process the completion request for zsh
+pub fn complete(cmd: &mut clap::Command, args: &CompleteArgs) -> clap::Result<()> {
+    let index = args.index.unwrap_or_default();
+    let _comp_type = args.comp_type.unwrap_or_default();
+    let _space = match (args.space, args.no_space) {
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        (true, true) => {
+            unreachable!("`--space` and `--no-space` set, clap should prevent this")
+        }
+        (false, false) => None,
+    }
+    .unwrap();
+
+    let current_dir = std::env::current_dir().ok();
+    let (completions, directive) =
+        super::complete::get(cmd, args.comp_words.clone(), index, current_dir.as_deref())?;
+    let completions =
+        super::complete_stacked_short_flags(cmd, &args.comp_words, index, completions);
+
+    let mut buf = Vec::new();
+    for completion in completions.iter() {
+        let value = completion.to_string_lossy();
+        match super::find_help(cmd, &value) {
+            Some(help) => writeln!(&mut buf, "{}\t{}", value, help)?,
+            None => writeln!(&mut buf, "{}", value)?,
+        }
+    }
+    // The last two lines are always directive metadata: a comma-separated list of file
+    // extensions (blank if none), then the flag bits. Extensions come *before* the flag bits,
+    // not after, since command substitution strips trailing empty lines and the extensions
+    // line is blank in the common case where no `file_ext` was set; the flag bits are never
+    // blank, so they're safe to put last.
+    let (directive_exts, directive_flags) = directive.encode();
+    writeln!(&mut buf, "{}", directive_exts)?;
+    writeln!(&mut buf, "{}", directive_flags)?;
+    std::io::stdout().write_all(&buf)?;
+
+    Ok(())
+}